@@ -10,11 +10,47 @@
 // copyright notice, and modified files need to carry a notice indicating
 // that they have been altered from the originals.
 
+use std::collections::{HashMap, HashSet};
+
 use pyo3::prelude::*;
-use pyo3::types::{PyList, PyString, PyTuple, PyType};
+use pyo3::types::{PyFloat, PyList, PyString, PyTuple, PyType};
+
+use qiskit_circuit::circuit_data::CircuitData;
+use qiskit_circuit::operations::{Operation, PackedOperation, Param, StandardGate};
+use qiskit_circuit::{Clbit, Qubit};
 
 use crate::error::QASM3ImporterError;
 
+/// A lookup from the identity of a Python bit object to its Rust-space index within whichever
+/// of `PyCircuit`'s qubits or clbits it was added to.  We key on the bit's pointer rather than
+/// hashing the object itself, since the bits are kept alive for the lifetime of the importer and
+/// their identity (not their value) is what defines their index.
+#[derive(Default)]
+struct BitIndexMap<T> {
+    indices: HashMap<usize, T>,
+}
+
+impl<T: Copy> BitIndexMap<T> {
+    fn reserve(&mut self, additional: usize) {
+        self.indices.reserve(additional);
+    }
+
+    fn insert(&mut self, bit: &PyAny, index: T) {
+        self.indices.insert(bit.as_ptr() as usize, index);
+    }
+
+    fn get(&self, bit: &PyAny) -> PyResult<T> {
+        self.indices
+            .get(&(bit.as_ptr() as usize))
+            .copied()
+            .ok_or_else(|| {
+                QASM3ImporterError::new_err(
+                    "internal logic error: bit was not added to the circuit before use",
+                )
+            })
+    }
+}
+
 macro_rules! register_type {
     ($name: ident) => {
         /// Rust-space wrapper around Qiskit `Register` objects.
@@ -41,6 +77,11 @@ macro_rules! register_type {
             pub fn iter<'a>(&'a self, py: Python<'a>) -> impl Iterator<Item = &'a PyAny> {
                 self.items.as_ref(py).iter()
             }
+
+            /// The number of bits in the register.
+            pub fn len(&self, py: Python) -> usize {
+                self.items.as_ref(py).len()
+            }
         }
 
         impl ::pyo3::IntoPy<Py<PyAny>> for $name {
@@ -158,6 +199,107 @@ impl PyGate {
     }
 }
 
+/// The `stdgates.inc` gate identifiers (plus the OpenQASM 3 builtins `U` and `gphase`), mapped
+/// to the native `StandardGate` they're equivalent to.  Keeping this as a flat table means
+/// `PyCircuitModule::import` can build the lookup once per importer rather than re-deriving it
+/// per gate application.
+static STANDARD_GATES: &[(&str, StandardGate)] = &[
+    ("p", StandardGate::PhaseGate),
+    ("x", StandardGate::XGate),
+    ("y", StandardGate::YGate),
+    ("z", StandardGate::ZGate),
+    ("h", StandardGate::HGate),
+    ("s", StandardGate::SGate),
+    ("sdg", StandardGate::SdgGate),
+    ("t", StandardGate::TGate),
+    ("tdg", StandardGate::TdgGate),
+    ("sx", StandardGate::SXGate),
+    ("rx", StandardGate::RXGate),
+    ("ry", StandardGate::RYGate),
+    ("rz", StandardGate::RZGate),
+    ("cx", StandardGate::CXGate),
+    ("cy", StandardGate::CYGate),
+    ("cz", StandardGate::CZGate),
+    ("cp", StandardGate::CPhaseGate),
+    ("crx", StandardGate::CRXGate),
+    ("cry", StandardGate::CRYGate),
+    ("crz", StandardGate::CRZGate),
+    ("ch", StandardGate::CHGate),
+    ("swap", StandardGate::SwapGate),
+    ("ccx", StandardGate::CCXGate),
+    ("cswap", StandardGate::CSwapGate),
+    ("cu", StandardGate::CUGate),
+    ("CX", StandardGate::CXGate),
+    ("phase", StandardGate::PhaseGate),
+    ("cphase", StandardGate::CPhaseGate),
+    ("id", StandardGate::IGate),
+    ("u1", StandardGate::U1Gate),
+    ("u2", StandardGate::U2Gate),
+    ("u3", StandardGate::U3Gate),
+    ("U", StandardGate::UGate),
+    ("gphase", StandardGate::GlobalPhaseGate),
+];
+
+/// Either a statically-known Rust-native gate, or a Python-space constructor for a gate that the
+/// program declared itself (which takes precedence even when the name is also in `stdgates.inc`).
+pub enum GateConstructor {
+    Standard(StandardGate),
+    Custom(PyGate),
+}
+
+impl GateConstructor {
+    pub fn name(&self) -> &str {
+        match self {
+            GateConstructor::Standard(gate) => gate.name(),
+            GateConstructor::Custom(gate) => gate.name(),
+        }
+    }
+
+    pub fn num_params(&self) -> usize {
+        match self {
+            GateConstructor::Standard(gate) => gate.num_params() as usize,
+            GateConstructor::Custom(gate) => gate.num_params(),
+        }
+    }
+
+    pub fn num_qubits(&self) -> usize {
+        match self {
+            GateConstructor::Standard(gate) => gate.num_qubits() as usize,
+            GateConstructor::Custom(gate) => gate.num_qubits(),
+        }
+    }
+
+    /// Build the `PackedOperation` for this gate.  The `Standard` arm is built purely Rust-side
+    /// from the gate alone and drops `params`, since a standard gate's parameters are stored on
+    /// the instruction (see [PyCircuit::append]) rather than on the operation; the `Custom` arm
+    /// keeps today's behaviour of calling back into Python to construct the user's gate instance.
+    pub fn construct(&self, py: Python, params: Vec<Param>) -> PyResult<PackedOperation> {
+        match self {
+            GateConstructor::Standard(gate) => Ok(PackedOperation::from_standard(*gate)),
+            GateConstructor::Custom(gate) => {
+                let py_params: Vec<Py<PyAny>> =
+                    params.into_iter().map(|param| param.into_py(py)).collect();
+                let instance = gate.construct(py, PyTuple::new(py, py_params))?;
+                Ok(PackedOperation::from_custom(
+                    py,
+                    instance,
+                    gate.name().to_owned(),
+                    gate.num_qubits() as u32,
+                    gate.num_params() as u32,
+                ))
+            }
+        }
+    }
+}
+
+/// A single instruction ready to be pushed onto a [PyCircuit], as used by [PyCircuit::extend].
+pub struct PreparedInstruction {
+    pub operation: PackedOperation,
+    pub qubits: Vec<Py<PyAny>>,
+    pub clbits: Vec<Py<PyAny>>,
+    pub params: Vec<Param>,
+}
+
 /// Wrapper around various Python-space imports. This is just a convenience wrapper to save us
 /// needing to `getattr` things off a Python-space module quite so frequently.  This is
 /// give-or-take just a manual lookup for a few `import` items at the top of a Python module, and
@@ -169,7 +311,7 @@ pub struct PyCircuitModule {
     qubit: Py<PyType>,
     creg: Py<PyType>,
     clbit: Py<PyType>,
-    instruction: Py<PyType>,
+    standard_gates: HashMap<&'static str, StandardGate>,
 }
 
 impl PyCircuitModule {
@@ -191,16 +333,70 @@ impl PyCircuitModule {
                 .downcast::<PyType>()?
                 .into_py(py),
             clbit: module.getattr("Clbit")?.downcast::<PyType>()?.into_py(py),
-            instruction: module
-                .getattr("CircuitInstruction")?
-                .downcast::<PyType>()?
-                .into_py(py),
+            standard_gates: STANDARD_GATES.iter().copied().collect(),
         })
     }
 
-    pub fn new_circuit(&self, py: Python) -> PyResult<PyCircuit> {
+    /// Resolve a gate application to either a native `StandardGate` or a Python-space
+    /// constructor.  `custom` must be `Some` whenever the *program itself* declared a gate of
+    /// this name (whether or not that name also happens to appear in `stdgates.inc`) — in that
+    /// case the program's own definition always wins, since a same-named, same-arity redefinition
+    /// of e.g. `swap` or `rz` is a real (if unusual) program and silently substituting the
+    /// standard gate would diverge from its semantics.  Only when `custom` is `None` — meaning
+    /// the name was never given a body and must therefore have come from a `stdgates.inc`
+    /// `include` — do we consult the standard-library registry at all.
+    pub fn lookup_gate(
+        &self,
+        name: &str,
+        num_params: usize,
+        num_qubits: usize,
+        custom: Option<PyGate>,
+    ) -> PyResult<GateConstructor> {
+        let Some(custom) = custom else {
+            let gate = self.standard_gates.get(name).ok_or_else(|| {
+                QASM3ImporterError::new_err(format!("no definition available for gate '{}'", name))
+            })?;
+            if gate.num_params() as usize != num_params || gate.num_qubits() as usize != num_qubits
+            {
+                return Err(QASM3ImporterError::new_err(format!(
+                    "'{}' is a standard-library gate taking {} parameter(s) and {} qubit(s), but was used with {} and {}",
+                    name,
+                    gate.num_params(),
+                    gate.num_qubits(),
+                    num_params,
+                    num_qubits,
+                )));
+            }
+            return Ok(GateConstructor::Standard(*gate));
+        };
+        Ok(GateConstructor::Custom(custom))
+    }
+
+    /// Start building a new circuit.  `num_qubits`, `num_clbits` and `instruction_capacity`
+    /// should be the totals the program is expected to contain, if known ahead of time, so the
+    /// backing `CircuitData` can preallocate once rather than growing incrementally as bits and
+    /// instructions trickle in.
+    pub fn new_circuit(
+        &self,
+        py: Python,
+        num_qubits: usize,
+        num_clbits: usize,
+        instruction_capacity: usize,
+    ) -> PyResult<PyCircuit> {
         Ok(PyCircuit {
-            qc: self.circuit.call0(py)?,
+            circuit_cls: self.circuit.clone_ref(py),
+            data: CircuitData::with_capacity(
+                py,
+                num_qubits,
+                num_clbits,
+                instruction_capacity,
+                Param::Float(0.0),
+            )?,
+            qregs: Vec::new(),
+            cregs: Vec::new(),
+            qubits: BitIndexMap::default(),
+            clbits: BitIndexMap::default(),
+            used_params: HashSet::new(),
         })
     }
 
@@ -243,67 +439,424 @@ impl PyCircuitModule {
     pub fn new_clbit(&self, py: Python) -> PyResult<Py<PyAny>> {
         self.clbit.call0(py)
     }
-
-    pub fn new_instruction<O, Q, C>(
-        &self,
-        py: Python,
-        operation: O,
-        qubits: Q,
-        clbits: C,
-    ) -> PyResult<Py<PyAny>>
-    where
-        O: IntoPy<Py<PyAny>>,
-        Q: IntoPy<Py<PyTuple>>,
-        C: IntoPy<Py<PyTuple>>,
-    {
-        self.instruction
-            .call1(py, (operation, qubits.into_py(py), clbits.into_py(py)))
-    }
 }
 
 /// Circuit construction context object to provide an easier Rust-space interface for us to
-/// construct the Python :class:`.QuantumCircuit`.  The idea of doing this from Rust space like
-/// this is that we might steadily be able to move more and more of it into being native Rust as
-/// the Rust-space APIs around the internal circuit data stabilise.
+/// construct the Python :class:`.QuantumCircuit`.  Rather than building the circuit up
+/// instruction-by-instruction through Python method calls, this accumulates a native
+/// `CircuitData` buffer; the Python-space circuit object itself is only created at hand-off in
+/// [PyCircuit::finish].
 pub struct PyCircuit {
-    /// The actual circuit object that's under construction.
-    qc: Py<PyAny>,
+    /// Cached handle to the `QuantumCircuit` type; used only to construct the output object
+    /// during hand-off in [PyCircuit::finish].
+    circuit_cls: Py<PyType>,
+    /// The instructions and bits accumulated so far, in the same native format backing
+    /// `QuantumCircuit._data`.
+    data: CircuitData,
+    /// Registers added to the circuit, attached to the output object during hand-off.
+    qregs: Vec<Py<PyAny>>,
+    cregs: Vec<Py<PyAny>>,
+    qubits: BitIndexMap<Qubit>,
+    clbits: BitIndexMap<Clbit>,
+    /// The identities of every `Parameter` referenced so far by an appended instruction's
+    /// parameters or already folded into the global phase, used by
+    /// [PyCircuit::ensure_parameters_tracked] to find declared parameters that never made it
+    /// into an operation.
+    used_params: HashSet<usize>,
 }
 
 impl PyCircuit {
     pub fn add_qreg(&mut self, py: Python, qreg: &PyQuantumRegister) -> PyResult<()> {
-        self.qc
-            .call_method1(py, "add_register", (qreg.to_object(py),))
-            .map(|_| ())
+        let len = qreg.len(py);
+        self.qubits.reserve(len);
+        self.data.reserve_qubits(len);
+        for bit in qreg.iter(py) {
+            let index = self.data.add_qubit(py, bit, true)?;
+            self.qubits.insert(bit, index);
+        }
+        self.qregs.push(qreg.to_object(py));
+        Ok(())
     }
 
     pub fn add_qubit(&mut self, py: Python, qubit: Py<PyAny>) -> PyResult<()> {
-        self.qc
-            .call_method1(py, "add_bits", ((qubit,),))
-            .map(|_| ())
+        let index = self.data.add_qubit(py, qubit.as_ref(py), true)?;
+        self.qubits.insert(qubit.as_ref(py), index);
+        Ok(())
+    }
+
+    /// Add several loose qubits in one go, reserving space for all of them up front rather than
+    /// growing the index map one bit at a time.
+    pub fn add_qubits<I>(&mut self, py: Python, qubits: I) -> PyResult<()>
+    where
+        I: IntoIterator<Item = Py<PyAny>>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let qubits = qubits.into_iter();
+        self.qubits.reserve(qubits.len());
+        self.data.reserve_qubits(qubits.len());
+        for qubit in qubits {
+            let index = self.data.add_qubit(py, qubit.as_ref(py), true)?;
+            self.qubits.insert(qubit.as_ref(py), index);
+        }
+        Ok(())
     }
 
     pub fn add_creg(&mut self, py: Python, creg: &PyClassicalRegister) -> PyResult<()> {
-        self.qc
-            .call_method1(py, "add_register", (creg.to_object(py),))
-            .map(|_| ())
+        let len = creg.len(py);
+        self.clbits.reserve(len);
+        self.data.reserve_clbits(len);
+        for bit in creg.iter(py) {
+            let index = self.data.add_clbit(py, bit, true)?;
+            self.clbits.insert(bit, index);
+        }
+        self.cregs.push(creg.to_object(py));
+        Ok(())
     }
 
     pub fn add_clbit<T: IntoPy<Py<PyAny>>>(&mut self, py: Python, clbit: T) -> PyResult<()> {
-        self.qc
-            .call_method1(py, "add_bits", ((clbit,),))
-            .map(|_| ())
+        let clbit = clbit.into_py(py);
+        let index = self.data.add_clbit(py, clbit.as_ref(py), true)?;
+        self.clbits.insert(clbit.as_ref(py), index);
+        Ok(())
     }
 
-    pub fn append<T: IntoPy<Py<PyAny>>>(&mut self, py: Python, instruction: T) -> PyResult<()> {
-        self.qc
-            .call_method1(py, "_append", (instruction.into_py(py),))
-            .map(|_| ())
+    /// Add several loose clbits in one go; see [PyCircuit::add_qubits].
+    pub fn add_clbits<I>(&mut self, py: Python, clbits: I) -> PyResult<()>
+    where
+        I: IntoIterator<Item = Py<PyAny>>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let clbits = clbits.into_iter();
+        self.clbits.reserve(clbits.len());
+        self.data.reserve_clbits(clbits.len());
+        for clbit in clbits {
+            let index = self.data.add_clbit(py, clbit.as_ref(py), true)?;
+            self.clbits.insert(clbit.as_ref(py), index);
+        }
+        Ok(())
+    }
+
+    /// Resolve `qubits` and `clbits` to their Rust-space indices and push a new
+    /// `PackedInstruction` directly onto the backing `CircuitData`, without building any
+    /// intermediate Python object.
+    ///
+    /// This deliberately does *not* maintain a separate interning cache for repeated qargs/cargs
+    /// lists: `CircuitData::push` already interns them internally, so a bespoke cache here would
+    /// only add a second hashmap and an extra allocation per call with no dedup benefit on top of
+    /// what `CircuitData` already does. An earlier version of this importer tried exactly that
+    /// and was reverted for that reason — closing out that idea rather than carrying it forward.
+    pub fn append(
+        &mut self,
+        py: Python,
+        operation: PackedOperation,
+        qubits: &[Py<PyAny>],
+        clbits: &[Py<PyAny>],
+        params: Vec<Param>,
+    ) -> PyResult<()> {
+        let qubits = qubits
+            .iter()
+            .map(|bit| self.qubits.get(bit.as_ref(py)))
+            .collect::<PyResult<Vec<_>>>()?;
+        let clbits = clbits
+            .iter()
+            .map(|bit| self.clbits.get(bit.as_ref(py)))
+            .collect::<PyResult<Vec<_>>>()?;
+        for param in &params {
+            self.mark_parameters_used(py, param)?;
+        }
+        self.data.push(py, operation, &qubits, &clbits, params)
+    }
+
+    /// Record the free `Parameter`s referenced by `param`, if any, as used.
+    fn mark_parameters_used(&mut self, py: Python, param: &Param) -> PyResult<()> {
+        let Param::ParameterExpression(expr) = param else {
+            return Ok(());
+        };
+        for free in expr.as_ref(py).getattr("parameters")?.iter()? {
+            self.used_params.insert(free?.as_ptr() as usize);
+        }
+        Ok(())
+    }
+
+    /// Append a whole batch of prepared instructions in one operation, reserving capacity in the
+    /// backing `CircuitData` from the known length up front.  This is the bulk counterpart to
+    /// [PyCircuit::append], used when replaying a `gate` body or a long straight-line sequence.
+    pub fn extend<I>(&mut self, py: Python, instructions: I) -> PyResult<()>
+    where
+        I: IntoIterator<Item = PreparedInstruction>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let instructions = instructions.into_iter();
+        self.data.reserve(instructions.len());
+        for instruction in instructions {
+            self.append(
+                py,
+                instruction.operation,
+                &instruction.qubits,
+                &instruction.clbits,
+                instruction.params,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Ensure every `Parameter` the importer created survives into the final circuit's
+    /// `parameters`, even if it was declared (as an OQ3 `input` or a `gate` parameter) but never
+    /// referenced by any instruction.  An unreferenced parameter is folded into the global phase
+    /// as a zero-weighted term: this is semantically a no-op, but keeps the symbol alive using
+    /// only the public `CircuitData` global-phase setter, rather than poking at any private
+    /// `QuantumCircuit` parameter bookkeeping.
+    pub fn ensure_parameters_tracked(&mut self, py: Python, params: &[Py<PyAny>]) -> PyResult<()> {
+        for param in params {
+            if self.used_params.contains(&(param.as_ptr() as usize)) {
+                continue;
+            }
+            // `zero_term` is always a `ParameterExpression`, so route the addition through its
+            // own `__radd__` rather than the current phase's `__add__`: a bare `float.__add__`
+            // doesn't know how to add a `ParameterExpression` and returns `NotImplemented`
+            // instead of raising or falling back, which would silently "succeed" with a
+            // `NotImplemented` object as the new global phase.
+            let zero_term = param.as_ref(py).call_method1("__rmul__", (0,))?;
+            let current_phase: Py<PyAny> = match self.data.global_phase() {
+                Param::Float(value) => PyFloat::new(py, *value).into_py(py),
+                Param::ParameterExpression(expr) => expr.clone_ref(py),
+                Param::Obj(obj) => obj.clone_ref(py),
+            };
+            let phase = zero_term.call_method1("__radd__", (current_phase,))?;
+            self.data
+                .set_global_phase(py, Param::ParameterExpression(phase.into_py(py)))?;
+            self.used_params.insert(param.as_ptr() as usize);
+        }
+        Ok(())
+    }
+
+    /// Consume this builder, constructing the finished Python-space `QuantumCircuit` and
+    /// installing the accumulated `CircuitData` and registers onto it.
+    pub fn finish(self, py: Python) -> PyResult<Py<PyAny>> {
+        let qc = self.circuit_cls.as_ref(py).call0()?;
+        qc.setattr("_data", Py::new(py, self.data)?)?;
+        for qreg in &self.qregs {
+            qc.call_method1("add_register", (qreg,))?;
+        }
+        for creg in &self.cregs {
+            qc.call_method1("add_register", (creg,))?;
+        }
+        Ok(qc.into_py(py))
     }
 }
 
-impl ::pyo3::IntoPy<Py<PyAny>> for PyCircuit {
-    fn into_py(self, py: Python) -> Py<PyAny> {
-        self.qc.clone_ref(py)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `lookup_gate` must prefer a program-declared custom gate over a same-named,
+    /// same-arity `stdgates.inc` entry: silently substituting the standard gate would diverge
+    /// from the program's actual semantics (e.g. a user-defined `swap`).
+    #[test]
+    fn lookup_gate_prefers_custom_over_standard_shadow() {
+        Python::with_gil(|py| {
+            let module = PyCircuitModule::import(py).unwrap();
+            let constructor = py.eval("lambda *args: None", None, None).unwrap();
+            let custom = PyGate::new(py, constructor, "swap".to_owned(), 0, 2);
+
+            let resolved = module
+                .lookup_gate("swap", 0, 2, Some(custom))
+                .expect("a declared custom gate should always resolve");
+            assert!(matches!(resolved, GateConstructor::Custom(_)));
+
+            let resolved = module
+                .lookup_gate("swap", 0, 2, None)
+                .expect("an undeclared name with a stdgates.inc match should resolve standard");
+            assert!(matches!(
+                resolved,
+                GateConstructor::Standard(StandardGate::SwapGate)
+            ));
+        });
+    }
+
+    /// A `gate` name that was never declared in the program and isn't in `stdgates.inc` has no
+    /// definition available at all.
+    #[test]
+    fn lookup_gate_rejects_unknown_name() {
+        Python::with_gil(|py| {
+            let module = PyCircuitModule::import(py).unwrap();
+            assert!(module.lookup_gate("not_a_gate", 0, 1, None).is_err());
+        });
+    }
+
+    /// Building a circuit through `PyCircuit` and handing it off via `finish` must preserve bit
+    /// ordering and register association, and the appended instruction must land on the right
+    /// qubits — none of which is exercised by going through `QuantumCircuit.append` any more,
+    /// since the importer no longer calls it.
+    #[test]
+    fn round_trip_preserves_bits_and_instructions() {
+        Python::with_gil(|py| {
+            let module = PyCircuitModule::import(py).unwrap();
+            let mut circuit = module.new_circuit(py, 0, 0, 1).unwrap();
+
+            let qreg = module.new_qreg(py, "q", 2).unwrap();
+            let creg = module.new_creg(py, "c", 2).unwrap();
+            circuit.add_qreg(py, &qreg).unwrap();
+            circuit.add_creg(py, &creg).unwrap();
+
+            let qubits = vec![qreg.bit(py, 0).unwrap(), qreg.bit(py, 1).unwrap()];
+            circuit
+                .append(
+                    py,
+                    PackedOperation::from_standard(StandardGate::CXGate),
+                    &qubits,
+                    &[],
+                    Vec::new(),
+                )
+                .unwrap();
+
+            let qc = circuit.finish(py).unwrap();
+            let qc = qc.as_ref(py);
+
+            assert_eq!(qc.getattr("num_qubits").unwrap().extract::<usize>().unwrap(), 2);
+            assert_eq!(qc.getattr("num_clbits").unwrap().extract::<usize>().unwrap(), 2);
+            assert_eq!(qc.getattr("qregs").unwrap().len().unwrap(), 1);
+            assert_eq!(qc.getattr("cregs").unwrap().len().unwrap(), 1);
+
+            let data = qc.getattr("data").unwrap();
+            assert_eq!(data.len().unwrap(), 1);
+            let instruction = data.get_item(0).unwrap();
+            let instruction_qubits = instruction.getattr("qubits").unwrap();
+            assert!(instruction_qubits
+                .get_item(0)
+                .unwrap()
+                .eq(qreg.bit(py, 0).unwrap())
+                .unwrap());
+            assert!(instruction_qubits
+                .get_item(1)
+                .unwrap()
+                .eq(qreg.bit(py, 1).unwrap())
+                .unwrap());
+        });
+    }
+
+    /// A declared `Parameter` that's never referenced by any instruction must still survive into
+    /// the finished circuit's `parameters`, folded into the global phase as a zero-weighted term.
+    #[test]
+    fn ensure_parameters_tracked_retains_unused_parameter() {
+        Python::with_gil(|py| {
+            let module = PyCircuitModule::import(py).unwrap();
+            let mut circuit = module.new_circuit(py, 0, 0, 0).unwrap();
+
+            let parameter_cls = py
+                .import("qiskit.circuit")
+                .unwrap()
+                .getattr("Parameter")
+                .unwrap();
+            let theta: Py<PyAny> = parameter_cls.call1(("theta",)).unwrap().into_py(py);
+
+            circuit
+                .ensure_parameters_tracked(py, &[theta.clone_ref(py)])
+                .unwrap();
+
+            let qc = circuit.finish(py).unwrap();
+            let parameters = qc.as_ref(py).getattr("parameters").unwrap();
+            assert_eq!(parameters.len().unwrap(), 1);
+            assert!(parameters
+                .get_item(0)
+                .unwrap()
+                .eq(theta.as_ref(py))
+                .unwrap());
+        });
+    }
+
+    /// `add_qubits`/`add_clbits` must register every loose bit passed to them (not just reserve
+    /// space for them), in the order given.
+    #[test]
+    fn add_qubits_and_add_clbits_register_all_bits() {
+        Python::with_gil(|py| {
+            let module = PyCircuitModule::import(py).unwrap();
+            let mut circuit = module.new_circuit(py, 0, 0, 0).unwrap();
+
+            let qubits = vec![module.new_qubit(py).unwrap(), module.new_qubit(py).unwrap()];
+            let clbits = vec![module.new_clbit(py).unwrap()];
+            let qubits_for_add: Vec<Py<PyAny>> =
+                qubits.iter().map(|bit| bit.clone_ref(py)).collect();
+            let clbits_for_add: Vec<Py<PyAny>> =
+                clbits.iter().map(|bit| bit.clone_ref(py)).collect();
+            circuit.add_qubits(py, qubits_for_add).unwrap();
+            circuit.add_clbits(py, clbits_for_add).unwrap();
+
+            let qc = circuit.finish(py).unwrap();
+            let qc = qc.as_ref(py);
+            assert_eq!(
+                qc.getattr("num_qubits").unwrap().extract::<usize>().unwrap(),
+                2
+            );
+            assert_eq!(
+                qc.getattr("num_clbits").unwrap().extract::<usize>().unwrap(),
+                1
+            );
+            assert!(qc
+                .getattr("qubits")
+                .unwrap()
+                .get_item(0)
+                .unwrap()
+                .eq(qubits[0].as_ref(py))
+                .unwrap());
+            assert!(qc
+                .getattr("clbits")
+                .unwrap()
+                .get_item(0)
+                .unwrap()
+                .eq(clbits[0].as_ref(py))
+                .unwrap());
+        });
+    }
+
+    /// `extend` must append every prepared instruction, in order, resolving each one's qubits
+    /// exactly as a sequence of individual `append` calls would.
+    #[test]
+    fn extend_appends_every_prepared_instruction_in_order() {
+        Python::with_gil(|py| {
+            let module = PyCircuitModule::import(py).unwrap();
+            let mut circuit = module.new_circuit(py, 0, 0, 0).unwrap();
+
+            let qreg = module.new_qreg(py, "q", 2).unwrap();
+            circuit.add_qreg(py, &qreg).unwrap();
+
+            let instructions = vec![
+                PreparedInstruction {
+                    operation: PackedOperation::from_standard(StandardGate::XGate),
+                    qubits: vec![qreg.bit(py, 0).unwrap()],
+                    clbits: Vec::new(),
+                    params: Vec::new(),
+                },
+                PreparedInstruction {
+                    operation: PackedOperation::from_standard(StandardGate::XGate),
+                    qubits: vec![qreg.bit(py, 1).unwrap()],
+                    clbits: Vec::new(),
+                    params: Vec::new(),
+                },
+            ];
+            circuit.extend(py, instructions).unwrap();
+
+            let qc = circuit.finish(py).unwrap();
+            let data = qc.as_ref(py).getattr("data").unwrap();
+            assert_eq!(data.len().unwrap(), 2);
+            assert!(data
+                .get_item(0)
+                .unwrap()
+                .getattr("qubits")
+                .unwrap()
+                .get_item(0)
+                .unwrap()
+                .eq(qreg.bit(py, 0).unwrap())
+                .unwrap());
+            assert!(data
+                .get_item(1)
+                .unwrap()
+                .getattr("qubits")
+                .unwrap()
+                .get_item(0)
+                .unwrap()
+                .eq(qreg.bit(py, 1).unwrap())
+                .unwrap());
+        });
     }
 }